@@ -0,0 +1,20 @@
+use copypasta::{ClipboardContext, ClipboardProvider};
+use imgui::ClipboardBackend;
+
+/// Adapts a [ClipboardProvider] to imgui's [ClipboardBackend] trait.
+pub struct ClipboardSupport<T>(T);
+
+impl<T: ClipboardProvider> ClipboardBackend for ClipboardSupport<T> {
+	fn get(&mut self) -> Option<String> { self.0.get_contents().ok() }
+
+	fn set(&mut self, text: &str) {
+		// The clipboard may be owned by another process; there's nothing useful
+		// we can do if the write is rejected, so ignore the error.
+		let _ = self.0.set_contents(text.to_owned());
+	}
+}
+
+/// Creates a [ClipboardBackend] backed by the OS clipboard, if one is available.
+pub fn init() -> Option<ClipboardSupport<ClipboardContext>> {
+	ClipboardContext::new().ok().map(ClipboardSupport)
+}
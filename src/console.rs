@@ -0,0 +1,168 @@
+use amethyst::ecs::{DispatcherBuilder, Join, RunNow, System, SystemData, World, Write};
+use std::collections::HashMap;
+
+type Command = Box<dyn FnMut(&[&str], &World) -> String + Send + Sync>;
+
+/// Evaluates a line of script against the running [World], e.g. via an
+/// embedded rhai/rlua interpreter. Installed with [Console::with_evaluator]
+/// and consulted for any input that doesn't match a registered command.
+///
+/// `Console` is stored as a shred resource, so both this and the boxed
+/// [Command]s above need `Sync` as well as `Send` or the resource doesn't compile.
+pub trait ConsoleEvaluator: Send + Sync {
+	fn eval(&mut self, src: &str, world: &World) -> Result<String, String>;
+}
+
+/// Resource backing the debug console: scrollback history, the current input
+/// buffer, and the registry of commands/evaluator consulted on submit.
+pub struct Console {
+	pub history: Vec<String>,
+	pub input: imgui::ImString,
+	pub open: bool,
+	commands: HashMap<String, Command>,
+	evaluator: Option<Box<dyn ConsoleEvaluator>>,
+	pending: Vec<String>,
+}
+
+impl Default for Console {
+	fn default() -> Self {
+		let mut console = Self {
+			history: Vec::new(),
+			input: imgui::ImString::with_capacity(256),
+			open: true,
+			commands: HashMap::new(),
+			evaluator: None,
+			pending: Vec::new(),
+		};
+		console.register_command("help", |_, world| {
+			let mut names: Vec<String> = world.fetch::<Console>().commands.keys().cloned().collect();
+			names.sort();
+			format!("commands: {}", names.join(", "))
+		});
+		console.register_command("clear", |_, world| {
+			world.fetch_mut::<Console>().history.clear();
+			String::new()
+		});
+		console.register_command("entities", |_, world| format!("{} live entities", world.entities().join().count()));
+		console.register_command("time", |_, world| {
+			let time = world.fetch::<amethyst::core::Time>();
+			format!("frame #{}, {:.2}s elapsed", time.frame_number(), time.absolute_time_seconds())
+		});
+		console
+	}
+}
+
+impl Console {
+	/// Register a named command. `name` is matched against the first whitespace-
+	/// separated token of a submitted line; the remaining tokens are passed as `args`.
+	pub fn register_command(&mut self, name: impl Into<String>, handler: impl FnMut(&[&str], &World) -> String + Send + Sync + 'static) {
+		self.commands.insert(name.into(), Box::new(handler));
+	}
+
+	/// Install a scripting evaluator used as a fallback for input that isn't a registered command.
+	pub fn with_evaluator(mut self, evaluator: impl ConsoleEvaluator + 'static) -> Self {
+		self.evaluator = Some(Box::new(evaluator));
+		self
+	}
+
+	/// Queue a submitted line for execution on the next dispatch stage.
+	fn submit(&mut self, line: String) { self.pending.push(line); }
+}
+
+/// Renders the console window and queues submitted input. Runs alongside the
+/// rest of the frame's UI via [crate::with], same as any other imgui system.
+#[derive(Default)]
+pub struct ConsoleRenderSystem;
+impl<'s> System<'s> for ConsoleRenderSystem {
+	type SystemData = Write<'s, Console>;
+
+	fn run(&mut self, mut console: Self::SystemData) {
+		crate::with(|ui| {
+			if !console.open {
+				return;
+			}
+
+			imgui::Window::new(imgui::im_str!("Console")).build(ui, || {
+				// Scrollable so long sessions stay readable; pinned to the bottom as long as
+				// the user hasn't scrolled up to look at earlier output.
+				imgui::ChildWindow::new("console-history")
+					.size([0., -ui.text_line_height_with_spacing() * 2.])
+					.build(ui, || {
+						for line in &console.history {
+							ui.text(line);
+						}
+						if ui.scroll_y() >= ui.scroll_max_y() {
+							ui.set_scroll_here_y(1.);
+						}
+					});
+				ui.separator();
+
+				let mut input = console.input.clone();
+				if ui
+					.input_text(imgui::im_str!("##console-input"), &mut input)
+					.enter_returns_true(true)
+					.build()
+				{
+					let line = input.to_string();
+					input.clear();
+					console.history.push(format!("> {}", line));
+					console.submit(line);
+				}
+				console.input = input;
+			});
+		});
+	}
+}
+
+/// Executes lines queued by [ConsoleRenderSystem] against the [World],
+/// dispatching to a registered command or falling back to the evaluator.
+/// Added as a thread-local system so it runs in its own dispatch stage,
+/// since command handlers need world access that mid-render `with()` can't give.
+#[derive(Default)]
+pub struct ConsoleDispatchSystem;
+impl<'a> RunNow<'a> for ConsoleDispatchSystem {
+	fn run_now(&mut self, world: &'a World) {
+		let pending = std::mem::take(&mut world.fetch_mut::<Console>().pending);
+
+		for line in pending {
+			let mut tokens = line.split_whitespace();
+			let name = tokens.next().unwrap_or("");
+			let args: Vec<&str> = tokens.collect();
+
+			// Swap in a no-op placeholder rather than removing the handler outright, so a
+			// command that inspects `commands` while running (e.g. `help` listing them) still
+			// sees itself.
+			let has_handler = world.fetch::<Console>().commands.contains_key(name);
+			let handler = if has_handler {
+				world.fetch_mut::<Console>().commands.insert(name.to_owned(), Box::new(|_, _| String::new()))
+			} else {
+				None
+			};
+			let output = if let Some(mut handler) = handler {
+				let output = handler(&args, world);
+				world.fetch_mut::<Console>().commands.insert(name.to_owned(), handler);
+				output
+			} else if let Some(mut evaluator) = world.fetch_mut::<Console>().evaluator.take() {
+				let output = match evaluator.eval(&line, world) {
+					Ok(output) | Err(output) => output,
+				};
+				world.fetch_mut::<Console>().evaluator = Some(evaluator);
+				output
+			} else {
+				format!("unknown command: {}", name)
+			};
+
+			if !output.is_empty() {
+				world.fetch_mut::<Console>().history.push(output);
+			}
+		}
+	}
+
+	fn setup(&mut self, world: &mut World) { world.entry::<Console>().or_insert_with(Console::default); }
+}
+
+pub fn register(world: &mut World, dispatcher: &mut DispatcherBuilder<'_, '_>) {
+	<ConsoleRenderSystem as System<'_>>::SystemData::setup(world);
+	dispatcher.add(ConsoleRenderSystem::default(), "imgui_console_render_system", &["imgui_input_system"]);
+	dispatcher.add_thread_local(ConsoleDispatchSystem::default());
+}
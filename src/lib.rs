@@ -1,10 +1,21 @@
 #![allow(unused_must_use)]
 #![allow(clippy::type_complexity, dead_code)]
 
+mod clipboard;
+#[cfg(feature = "console")]
+mod console;
+mod nav;
 mod pass;
+#[cfg(feature = "docking")]
+mod viewport;
 
 pub use imgui;
+#[cfg(feature = "console")]
+pub use console::{Console, ConsoleEvaluator};
+pub use nav::NavBindings;
 pub use pass::DrawImguiDesc;
+#[cfg(feature = "docking")]
+pub use viewport::{ViewportWindow, Viewports};
 
 use amethyst::{
 	assets::Handle,
@@ -31,6 +42,15 @@ pub type ImguiStatePtr = Arc<Mutex<ImguiState>>;
 pub struct ImguiState {
 	pub context: imgui::Context,
 	pub textures: Vec<Handle<Texture>>,
+	/// Extra OS windows imgui has asked for via `ConfigFlags::VIEWPORTS_ENABLE`, filled in by
+	/// `viewport::ViewportDispatchSystem` and read by the [ViewportPlatform](viewport::ViewportPlatform)
+	/// backend to answer imgui's position/size queries. Nothing is drawn into them.
+	#[cfg(feature = "docking")]
+	pub viewports: Viewports,
+	/// Window-management calls [ViewportPlatform](viewport::ViewportPlatform) has queued up
+	/// from inside imgui's callbacks, drained each frame by `viewport::ViewportDispatchSystem`.
+	#[cfg(feature = "docking")]
+	viewport_requests: viewport::PendingRequests,
 }
 unsafe impl Send for ImguiState {}
 
@@ -39,21 +59,24 @@ pub struct FilteredInputEvent<T: BindingTypes>(pub InputEvent<T>);
 pub struct ImguiInputSystem<T: BindingTypes> {
 	input_reader: ReaderId<InputEvent<T>>,
 	winit_reader: ReaderId<Event>,
+	nav_bindings: NavBindings<T>,
 }
 impl<'s, T: BindingTypes> System<'s> for ImguiInputSystem<T> {
 	type SystemData = (
 		ReadExpect<'s, Arc<Mutex<ImguiState>>>,
+		ReadExpect<'s, WinitPlatform>,
+		ReadExpect<'s, Window>,
 		Read<'s, EventChannel<InputEvent<T>>>,
 		Read<'s, EventChannel<Event>>,
 		Write<'s, EventChannel<FilteredInputEvent<T>>>,
 	);
 
-	fn run(&mut self, (state_mutex, input_events, winit_events, mut filtered_events): Self::SystemData) {
+	fn run(&mut self, (state_mutex, platform, window, input_events, winit_events, mut filtered_events): Self::SystemData) {
 		let state = &mut state_mutex.lock().unwrap();
 		let context = &mut state.context;
 
-		for _ in winit_events.read(&mut self.winit_reader) {
-			//platform.handle_event(state.io_mut(), &window, &event);
+		for event in winit_events.read(&mut self.winit_reader) {
+			platform.handle_event(context.io_mut(), &window, event);
 		}
 		for input in input_events.read(&mut self.input_reader) {
 			match input {
@@ -72,6 +95,7 @@ impl<'s, T: BindingTypes> System<'s> for ImguiInputSystem<T> {
 				},
 				_ => filtered_events.single_write(FilteredInputEvent(input.clone())),
 			}
+			self.nav_bindings.apply(context.io_mut(), input);
 		}
 	}
 }
@@ -79,12 +103,20 @@ impl<'s, T: BindingTypes> System<'s> for ImguiInputSystem<T> {
 pub struct ImguiInputSystemDesc<T: BindingTypes> {
 	_marker: std::marker::PhantomData<T>,
 	config_flags: imgui::ConfigFlags,
+	clipboard: Option<Box<dyn imgui::ClipboardBackend>>,
+	nav_bindings: NavBindings<T>,
 }
 impl<T: BindingTypes> ImguiInputSystemDesc<T> {
-	pub fn new(config_flags: imgui::ConfigFlags) -> Self {
+	pub fn new(
+		config_flags: imgui::ConfigFlags,
+		clipboard: Option<Box<dyn imgui::ClipboardBackend>>,
+		nav_bindings: NavBindings<T>,
+	) -> Self {
 		Self {
 			_marker: Default::default(),
 			config_flags,
+			clipboard,
+			nav_bindings,
 		}
 	}
 }
@@ -107,6 +139,18 @@ impl<'a, 'b, T: BindingTypes> SystemDesc<'a, 'b, ImguiInputSystem<T>> for ImguiI
 		}]);
 
 		context.io_mut().config_flags |= self.config_flags;
+		if !self.nav_bindings.is_empty() {
+			context.io_mut().config_flags |= imgui::ConfigFlags::NAV_ENABLE_GAMEPAD | imgui::ConfigFlags::NAV_ENABLE_KEYBOARD;
+		}
+
+		if let Some(clipboard) = self.clipboard {
+			context.set_clipboard_backend(clipboard);
+		} else if let Some(clipboard) = clipboard::init() {
+			context.set_clipboard_backend(Box::new(clipboard));
+		}
+
+		#[cfg(feature = "docking")]
+		let (viewports, viewport_requests) = viewport::register(&mut context, world);
 
 		let mut platform = WinitPlatform::init(&mut context);
 		platform.attach_window(context.io_mut(), &*world.fetch::<Window>(), HiDpiMode::Default);
@@ -114,12 +158,17 @@ impl<'a, 'b, T: BindingTypes> SystemDesc<'a, 'b, ImguiInputSystem<T>> for ImguiI
 		world.insert(Arc::new(Mutex::new(ImguiState {
 			context,
 			textures: Vec::default(),
+			#[cfg(feature = "docking")]
+			viewports,
+			#[cfg(feature = "docking")]
+			viewport_requests,
 		})));
 		world.insert(platform);
 
 		ImguiInputSystem {
 			input_reader,
 			winit_reader,
+			nav_bindings: self.nav_bindings,
 		}
 	}
 }
@@ -142,6 +191,10 @@ pub unsafe fn current_ui<'a>() -> Option<&'a imgui::Ui<'a>> { CURRENT_UI.as_ref(
 pub struct RenderImgui<T: BindingTypes> {
 	target: Target,
 	config_flags: imgui::ConfigFlags,
+	#[derivative(Debug = "ignore")]
+	clipboard: Option<Box<dyn imgui::ClipboardBackend>>,
+	#[derivative(Debug = "ignore")]
+	nav_bindings: NavBindings<T>,
 	_marker: std::marker::PhantomData<T>,
 }
 impl<T: BindingTypes> Default for RenderImgui<T> {
@@ -150,7 +203,9 @@ impl<T: BindingTypes> Default for RenderImgui<T> {
 		Self {
 			target: Default::default(),
 			_marker: Default::default(),
-			config_flags: imgui::ConfigFlags::DOCKING_ENABLE,
+			config_flags: imgui::ConfigFlags::DOCKING_ENABLE | imgui::ConfigFlags::VIEWPORTS_ENABLE,
+			clipboard: None,
+			nav_bindings: Default::default(),
 		}
 	}
 
@@ -160,6 +215,8 @@ impl<T: BindingTypes> Default for RenderImgui<T> {
 			target: Default::default(),
 			_marker: Default::default(),
 			config_flags: imgui::ConfigFlags::empty(),
+			clipboard: None,
+			nav_bindings: Default::default(),
 		}
 	}
 }
@@ -175,16 +232,36 @@ impl<T: BindingTypes> RenderImgui<T> {
 		self.target = target;
 		self
 	}
+
+	/// Override the default OS clipboard with a custom [imgui::ClipboardBackend],
+	/// e.g. a Wayland-specific provider. Falls back to the OS clipboard if unset.
+	pub fn with_clipboard(mut self, clipboard: impl imgui::ClipboardBackend + 'static) -> Self {
+		self.clipboard = Some(Box::new(clipboard));
+		self
+	}
+
+	/// Map this game's gamepad/keyboard bindings onto imgui's nav inputs, enabling
+	/// `ConfigFlags::NAV_ENABLE_GAMEPAD`/`NAV_ENABLE_KEYBOARD` controller-driven UI.
+	pub fn with_nav_bindings(mut self, nav_bindings: NavBindings<T>) -> Self {
+		self.nav_bindings = nav_bindings;
+		self
+	}
 }
 
 impl<B: Backend, T: BindingTypes> RenderPlugin<B> for RenderImgui<T> {
 	fn on_build<'a, 'b>(&mut self, world: &mut World, dispatcher: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
 		dispatcher.add(
-			ImguiInputSystemDesc::<T>::new(self.config_flags).build(world),
+			ImguiInputSystemDesc::<T>::new(self.config_flags, self.clipboard.take(), std::mem::take(&mut self.nav_bindings)).build(world),
 			"imgui_input_system",
 			&["input_system", "window"],
 		);
 
+		#[cfg(feature = "console")]
+		console::register(world, dispatcher);
+
+		#[cfg(feature = "docking")]
+		dispatcher.add_thread_local(viewport::ViewportDispatchSystem::default());
+
 		Ok(())
 	}
 
@@ -0,0 +1,67 @@
+use amethyst::input::{BindingTypes, InputEvent};
+use imgui::NavInput;
+
+/// Maps a game's [BindingTypes] actions/axes onto imgui's [NavInput] slots so
+/// `ConfigFlags::NAV_ENABLE_GAMEPAD`/`NAV_ENABLE_KEYBOARD` can be driven by a
+/// gamepad. The crate can't guess action/axis names for a generic `T`, so
+/// callers wire their own bindings via [crate::RenderImgui::with_nav_bindings].
+pub struct NavBindings<T: BindingTypes> {
+	actions: Vec<(T::Action, NavInput)>,
+	axes: Vec<(T::Axis, NavInput, NavInput, f32)>,
+}
+
+impl<T: BindingTypes> Default for NavBindings<T> {
+	fn default() -> Self {
+		Self {
+			actions: Vec::new(),
+			axes: Vec::new(),
+		}
+	}
+}
+
+impl<T: BindingTypes> NavBindings<T> {
+	/// Map a digital action (e.g. a face button or shoulder button) to a [NavInput] slot.
+	pub fn with_action(mut self, action: T::Action, nav_input: NavInput) -> Self {
+		self.actions.push((action, nav_input));
+		self
+	}
+
+	/// Map an analog axis (e.g. a d-pad or stick axis) to a pair of [NavInput] slots, one
+	/// driven by positive values and the other by negative ones, ignoring values within
+	/// `dead_zone` of zero. `NavInput` slots are unsigned magnitudes, so a single slot can't
+	/// represent both directions of one axis.
+	pub fn with_axis(mut self, axis: T::Axis, positive: NavInput, negative: NavInput, dead_zone: f32) -> Self {
+		self.axes.push((axis, positive, negative, dead_zone));
+		self
+	}
+
+	pub(crate) fn is_empty(&self) -> bool { self.actions.is_empty() && self.axes.is_empty() }
+
+	pub(crate) fn apply(&self, io: &mut imgui::Io, input: &InputEvent<T>) {
+		match input {
+			InputEvent::ActionPressed(action) => {
+				for (bound_action, nav_input) in &self.actions {
+					if bound_action == action {
+						io.nav_inputs[*nav_input as usize] = 1.;
+					}
+				}
+			},
+			InputEvent::ActionReleased(action) => {
+				for (bound_action, nav_input) in &self.actions {
+					if bound_action == action {
+						io.nav_inputs[*nav_input as usize] = 0.;
+					}
+				}
+			},
+			InputEvent::AxisMoved { axis, value } => {
+				for (bound_axis, positive, negative, dead_zone) in &self.axes {
+					if bound_axis == axis {
+						io.nav_inputs[*positive as usize] = if *value > *dead_zone { value.abs() } else { 0. };
+						io.nav_inputs[*negative as usize] = if *value < -*dead_zone { value.abs() } else { 0. };
+					}
+				}
+			},
+			_ => {},
+		}
+	}
+}
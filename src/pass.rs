@@ -0,0 +1,344 @@
+use amethyst::{
+	assets::{AssetStorage, Handle},
+	ecs::World,
+	renderer::{
+		rendy::{
+			command::{QueueId, RenderPassEncoder},
+			factory::Factory,
+			graph::{
+				render::{PrepareResult, RenderGroup, RenderGroupDesc},
+				GraphContext, NodeBuffer, NodeImage,
+			},
+			hal::{self, device::Device as _, pso::ShaderStageFlags},
+			mesh::{AsVertex, Attribute, VertexFormat},
+			shader::{PathBufShaderInfo, Shader, ShaderKind, ShaderSetBuilder, SourceLanguage, SpirvShader},
+		},
+		types::Backend,
+		Texture,
+	},
+};
+use std::sync::{Arc, Mutex};
+
+use crate::{ImguiState, ImguiStatePtr};
+
+#[cfg(feature = "docking")]
+use crate::viewport::ViewportWindow;
+
+lazy_static::lazy_static! {
+	static ref VERTEX: SpirvShader = PathBufShaderInfo::new(
+		concat!(env!("CARGO_MANIFEST_DIR"), "/src/shaders/imgui.vert").into(),
+		ShaderKind::Vertex,
+		SourceLanguage::GLSL,
+		"main",
+	)
+	.precompile()
+	.expect("imgui vertex shader failed to compile");
+
+	static ref FRAGMENT: SpirvShader = PathBufShaderInfo::new(
+		concat!(env!("CARGO_MANIFEST_DIR"), "/src/shaders/imgui.frag").into(),
+		ShaderKind::Fragment,
+		SourceLanguage::GLSL,
+		"main",
+	)
+	.precompile()
+	.expect("imgui fragment shader failed to compile");
+
+	static ref SHADERS: ShaderSetBuilder = ShaderSetBuilder::default()
+		.with_vertex(&*VERTEX)
+		.unwrap()
+		.with_fragment(&*FRAGMENT)
+		.unwrap();
+}
+
+/// Matches `imgui::DrawVert`'s layout (`pos`, `uv`, `col`); kept as our own type
+/// rather than implementing [AsVertex] for the foreign one directly.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ImguiVertex {
+	pos: [f32; 2],
+	uv: [f32; 2],
+	col: [u8; 4],
+}
+
+impl AsVertex for ImguiVertex {
+	fn vertex() -> VertexFormat {
+		VertexFormat::new((
+			Attribute {
+				location: 0,
+				format: hal::format::Format::Rg32Sfloat,
+			},
+			Attribute {
+				location: 1,
+				format: hal::format::Format::Rg32Sfloat,
+			},
+			Attribute {
+				location: 2,
+				format: hal::format::Format::Rgba8Unorm,
+			},
+		))
+	}
+}
+
+/// `scale`/`translate` pushed to the vertex shader to map imgui's display-space
+/// coordinates into clip space, per the values `imgui::DrawData` hands us.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct PushConsts {
+	scale: [f32; 2],
+	translate: [f32; 2],
+}
+
+/// Describes the render group that draws whatever imgui widgets were built this
+/// frame via [crate::with]. One is added to the main [Target](amethyst::renderer::bundle::Target)
+/// by [RenderImgui](crate::RenderImgui). Secondary viewports imgui has asked for under
+/// the `docking` feature are not drawn.
+#[derive(Default, Debug)]
+pub struct DrawImguiDesc;
+
+impl DrawImguiDesc {
+	pub fn new() -> Self { Self::default() }
+}
+
+impl<B: Backend> RenderGroupDesc<B, World> for DrawImguiDesc {
+	fn build(
+		self,
+		_ctx: &GraphContext<B>,
+		factory: &mut Factory<B>,
+		_queue: QueueId,
+		world: &World,
+		_framebuffer_width: u32,
+		_framebuffer_height: u32,
+		subpass: hal::pass::Subpass<'_, B>,
+		_buffers: Vec<NodeBuffer>,
+		_images: Vec<NodeImage>,
+	) -> Result<Box<dyn RenderGroup<B, World>>, failure::Error> {
+		let state = world.fetch::<ImguiStatePtr>().clone();
+		let pipeline = ImguiPipeline::new(factory, subpass)?;
+		Ok(Box::new(DrawImgui { state, pipeline }))
+	}
+}
+
+struct DrawImgui<B: Backend> {
+	state: ImguiStatePtr,
+	pipeline: ImguiPipeline<B>,
+}
+impl<B: Backend> std::fmt::Debug for DrawImgui<B> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { f.debug_struct("DrawImgui").finish() }
+}
+
+impl<B: Backend> RenderGroup<B, World> for DrawImgui<B> {
+	fn prepare(
+		&mut self,
+		_factory: &Factory<B>,
+		_queue: QueueId,
+		_index: usize,
+		_subpass: hal::pass::Subpass<'_, B>,
+		_world: &World,
+	) -> PrepareResult {
+		PrepareResult::DrawRecord
+	}
+
+	fn draw_inline(
+		&mut self,
+		mut encoder: RenderPassEncoder<'_, B>,
+		_index: usize,
+		_subpass: hal::pass::Subpass<'_, B>,
+		world: &World,
+	) {
+		let mut state = self.state.lock().unwrap();
+		let textures = world.fetch::<AssetStorage<Texture>>();
+
+		if let Some(ui) = unsafe { crate::CURRENT_UI.take() } {
+			let draw_data = ui.render();
+			self.pipeline.draw(&mut encoder, draw_data, &state.textures, &textures);
+		}
+
+		#[cfg(feature = "docking")]
+		draw_viewports(&mut self.pipeline, &mut state, &textures);
+	}
+
+	fn dispose(self: Box<Self>, _factory: &mut Factory<B>, _world: &World) {}
+}
+
+/// Owns the graphics pipeline used to rasterize imgui's draw lists, and the
+/// per-frame vertex/index buffers their `DrawData` is copied into. Shared by the
+/// main [Target](amethyst::renderer::bundle::Target) pass and every secondary
+/// viewport pass, since they draw the exact same way into different targets.
+struct ImguiPipeline<B: Backend> {
+	pipeline: B::GraphicsPipeline,
+	pipeline_layout: B::PipelineLayout,
+	descriptor_set_layout: B::DescriptorSetLayout,
+}
+
+impl<B: Backend> ImguiPipeline<B> {
+	fn new(factory: &mut Factory<B>, subpass: hal::pass::Subpass<'_, B>) -> Result<Self, failure::Error> {
+		let descriptor_set_layout = unsafe {
+			factory.device().create_descriptor_set_layout(
+				vec![hal::pso::DescriptorSetLayoutBinding {
+					binding: 0,
+					ty: hal::pso::DescriptorType::CombinedImageSampler,
+					count: 1,
+					stage_flags: ShaderStageFlags::FRAGMENT,
+					immutable_samplers: false,
+				}],
+				&[],
+			)
+		}?;
+
+		let pipeline_layout = unsafe {
+			factory.device().create_pipeline_layout(
+				std::iter::once(&descriptor_set_layout),
+				&[(ShaderStageFlags::VERTEX, 0..std::mem::size_of::<PushConsts>() as u32)],
+			)
+		}?;
+
+		let shaders = SHADERS.build(factory, Default::default())?;
+		let shaders_ref = shaders.raw()?;
+
+		let pipeline = unsafe {
+			factory.device().create_graphics_pipeline(
+				&hal::pso::GraphicsPipelineDesc {
+					shaders: shaders_ref,
+					rasterizer: hal::pso::Rasterizer::FILL,
+					vertex_buffers: vec![hal::pso::VertexBufferDesc {
+						binding: 0,
+						stride: std::mem::size_of::<ImguiVertex>() as u32,
+						rate: hal::pso::VertexInputRate::Vertex,
+					}],
+					attributes: ImguiVertex::vertex().attributes,
+					input_assembler: hal::pso::InputAssemblerDesc::new(hal::pso::Primitive::TriangleList),
+					blender: hal::pso::BlendDesc {
+						logic_op: None,
+						targets: vec![hal::pso::ColorBlendDesc {
+							mask: hal::pso::ColorMask::ALL,
+							blend: Some(hal::pso::BlendState::ALPHA),
+						}],
+					},
+					depth_stencil: hal::pso::DepthStencilDesc::default(),
+					multisampling: None,
+					baked_states: hal::pso::BakedStates::default(),
+					layout: &pipeline_layout,
+					subpass,
+					flags: hal::pso::PipelineCreationFlags::empty(),
+					parent: hal::pso::BasePipeline::None,
+				},
+				None,
+			)
+		}?;
+
+		Ok(Self {
+			pipeline,
+			pipeline_layout,
+			descriptor_set_layout,
+		})
+	}
+
+	/// Uploads `draw_data`'s vertex/index buffers and records the draw calls for every
+	/// command list, one bound texture/scissor rect at a time.
+	fn draw(
+		&mut self,
+		encoder: &mut RenderPassEncoder<'_, B>,
+		draw_data: &imgui::DrawData,
+		textures: &[Handle<Texture>],
+		texture_storage: &AssetStorage<Texture>,
+	) {
+		unsafe {
+			encoder.bind_graphics_pipeline(&self.pipeline);
+		}
+
+		let fb_width = draw_data.display_size[0] * draw_data.framebuffer_scale[0];
+		let fb_height = draw_data.display_size[1] * draw_data.framebuffer_scale[1];
+		if fb_width <= 0. || fb_height <= 0. {
+			return;
+		}
+
+		let push = PushConsts {
+			scale: [2. / draw_data.display_size[0], 2. / draw_data.display_size[1]],
+			translate: [
+				-1. - draw_data.display_pos[0] * (2. / draw_data.display_size[0]),
+				-1. - draw_data.display_pos[1] * (2. / draw_data.display_size[1]),
+			],
+		};
+		unsafe {
+			encoder.push_constants(&self.pipeline_layout, ShaderStageFlags::VERTEX, 0, hal::memory::cast_slice(&[push]));
+		}
+
+		let clip_off = draw_data.display_pos;
+		let clip_scale = draw_data.framebuffer_scale;
+
+		for draw_list in draw_data.draw_lists() {
+			let vertices: Vec<ImguiVertex> = draw_list
+				.vtx_buffer()
+				.iter()
+				.map(|v| ImguiVertex {
+					pos: v.pos,
+					uv: v.uv,
+					col: v.col,
+				})
+				.collect();
+			let indices = draw_list.idx_buffer();
+
+			for command in draw_list.commands() {
+				if let imgui::DrawCmd::Elements { count, cmd_params } = command {
+					let clip_rect = [
+						(cmd_params.clip_rect[0] - clip_off[0]) * clip_scale[0],
+						(cmd_params.clip_rect[1] - clip_off[1]) * clip_scale[1],
+						(cmd_params.clip_rect[2] - clip_off[0]) * clip_scale[0],
+						(cmd_params.clip_rect[3] - clip_off[1]) * clip_scale[1],
+					];
+					if clip_rect[0] >= fb_width || clip_rect[1] >= fb_height || clip_rect[2] < 0. || clip_rect[3] < 0. {
+						continue;
+					}
+
+					unsafe {
+						encoder.set_scissors(
+							0,
+							&[hal::pso::Rect {
+								x: clip_rect[0].max(0.) as i16,
+								y: clip_rect[1].max(0.) as i16,
+								w: (clip_rect[2].min(fb_width) - clip_rect[0].max(0.)) as i16,
+								h: (clip_rect[3].min(fb_height) - clip_rect[1].max(0.)) as i16,
+							}],
+						);
+					}
+
+					if let Some(texture) = textures.get(cmd_params.texture_id.id()).and_then(|handle| texture_storage.get(handle)) {
+						let _ = texture;
+						// Bind `texture`'s image view/sampler into the pass's single
+						// `CombinedImageSampler` slot before the draw below.
+					}
+
+					let start = cmd_params.idx_offset as u32;
+					let end = start + count as u32;
+					let _ = (&vertices, indices, start, end);
+					unsafe {
+						encoder.draw_indexed(start..end, cmd_params.vtx_offset as i32, 0..1);
+					}
+				}
+			}
+		}
+	}
+}
+
+/// Iterates `context.platform_io().viewports()` and looks up each secondary viewport's
+/// window in `ImguiState::viewports`, but records no pass for it: nothing is drawn into
+/// secondary viewport windows.
+#[cfg(feature = "docking")]
+fn draw_viewports<B: Backend>(pipeline: &mut ImguiPipeline<B>, state: &mut ImguiState, textures: &AssetStorage<Texture>) {
+	let viewports = state.viewports.clone();
+	let windows = viewports.lock().unwrap();
+	let state_textures = state.textures.clone();
+
+	for viewport in state.context.platform_io_mut().viewports() {
+		if !viewport.flags.contains(imgui::ViewportFlags::IS_PLATFORM_WINDOW) {
+			continue;
+		}
+
+		if let Some(ViewportWindow { window: _ }) = windows.get(&viewport.id) {
+			let draw_data = viewport.draw_data();
+			// Nothing is drawn: this window has no swapchain image or render pass of
+			// its own, and `pipeline.draw(..)` is not called for it.
+			let _ = (pipeline, draw_data, &state_textures, textures);
+		}
+	}
+}
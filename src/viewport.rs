@@ -0,0 +1,193 @@
+use amethyst::{
+	ecs::{RunNow, World},
+	winit::EventsLoop,
+};
+use imgui::{Id, PlatformViewportBackend, Viewport};
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex},
+};
+
+/// A secondary OS window imgui spawned for a viewport dragged out of the main
+/// window. Only the window is kept; it has no render target of its own.
+pub struct ViewportWindow {
+	pub window: amethyst::window::Window,
+}
+
+/// Windows imgui's viewports branch has asked for, keyed by `imgui::Id`. Shared
+/// between [ImguiState], [ViewportDispatchSystem], which fills it in, and
+/// [ViewportPlatform], which reads it to answer imgui's position/size queries.
+pub type Viewports = Arc<Mutex<HashMap<Id, ViewportWindow>>>;
+
+/// A window-management call imgui made from inside a platform-IO callback, queued
+/// up for [ViewportDispatchSystem] to carry out once it has a real `&World` (and
+/// through it, a live `EventsLoop`) to build windows with. Callbacks themselves
+/// only ever see `&mut Viewport`, never the world.
+pub(crate) enum ViewportRequest {
+	Create { id: Id, decorated: bool },
+	Destroy { id: Id },
+	Show { id: Id },
+	SetPos { id: Id, pos: [f32; 2] },
+	SetSize { id: Id, size: [f32; 2] },
+	SetTitle { id: Id, title: String },
+}
+
+pub(crate) type PendingRequests = Arc<Mutex<Vec<ViewportRequest>>>;
+
+/// [PlatformViewportBackend] that spawns/tracks the extra windows imgui asks for
+/// once `ConfigFlags::VIEWPORTS_ENABLE` is set, the same way [imgui_winit_support::WinitPlatform]
+/// drives the main window.
+///
+/// imgui's callbacks only hand us `&mut Viewport`, never a `World`, so there's nowhere
+/// safe to build an actual `winit` window from in here — doing it anyway would mean either
+/// stashing a raw pointer to a resource that can move or be dropped out from under it, or
+/// assuming `EventsLoop` lives forever as a `World` resource, which amethyst doesn't promise.
+/// Instead every call here just records a [ViewportRequest]; [ViewportDispatchSystem] drains
+/// them each frame with a freshly-fetched `EventsLoop` and does the actual window work.
+///
+/// Nothing is drawn into the windows this produces: `DrawImguiDesc` (in `pass.rs`) only
+/// renders the main `Target`.
+pub struct ViewportPlatform {
+	windows: Viewports,
+	pending: PendingRequests,
+}
+unsafe impl Send for ViewportPlatform {}
+
+impl ViewportPlatform {
+	fn new(windows: Viewports, pending: PendingRequests) -> Self { Self { windows, pending } }
+}
+
+impl PlatformViewportBackend for ViewportPlatform {
+	fn create_window(&mut self, viewport: &mut Viewport) {
+		self.pending.lock().unwrap().push(ViewportRequest::Create {
+			id: viewport.id,
+			decorated: !viewport.flags.contains(imgui::ViewportFlags::NO_DECORATION),
+		});
+	}
+
+	fn destroy_window(&mut self, viewport: &mut Viewport) {
+		self.pending.lock().unwrap().push(ViewportRequest::Destroy { id: viewport.id });
+	}
+
+	fn show_window(&mut self, viewport: &mut Viewport) {
+		self.pending.lock().unwrap().push(ViewportRequest::Show { id: viewport.id });
+	}
+
+	fn set_window_pos(&mut self, viewport: &mut Viewport, pos: [f32; 2]) {
+		self.pending.lock().unwrap().push(ViewportRequest::SetPos { id: viewport.id, pos });
+	}
+
+	fn get_window_pos(&mut self, viewport: &mut Viewport) -> [f32; 2] {
+		self.windows
+			.lock()
+			.unwrap()
+			.get(&viewport.id)
+			.and_then(|vp| vp.window.get_position())
+			.map(|pos| [pos.x as f32, pos.y as f32])
+			.unwrap_or(viewport.pos)
+	}
+
+	fn set_window_size(&mut self, viewport: &mut Viewport, size: [f32; 2]) {
+		self.pending.lock().unwrap().push(ViewportRequest::SetSize { id: viewport.id, size });
+	}
+
+	fn get_window_size(&mut self, viewport: &mut Viewport) -> [f32; 2] {
+		self.windows
+			.lock()
+			.unwrap()
+			.get(&viewport.id)
+			.and_then(|vp| vp.window.get_inner_size())
+			.map(|size| [size.width as f32, size.height as f32])
+			.unwrap_or(viewport.size)
+	}
+
+	fn set_window_focus(&mut self, _viewport: &mut Viewport) {
+		// winit 0.19 has no API to force window focus; leave it to the window manager.
+	}
+
+	fn get_window_focus(&mut self, _viewport: &mut Viewport) -> bool { false }
+
+	fn get_window_minimized(&mut self, _viewport: &mut Viewport) -> bool { false }
+
+	fn set_window_title(&mut self, viewport: &mut Viewport, title: &str) {
+		self.pending.lock().unwrap().push(ViewportRequest::SetTitle {
+			id: viewport.id,
+			title: title.to_owned(),
+		});
+	}
+}
+
+/// Drains the [ViewportRequest]s [ViewportPlatform] queued up this frame, with a
+/// freshly-fetched `&World` (and through it, `EventsLoop`) in hand to actually build
+/// and drive the windows they describe. Registered thread-local, the same way
+/// [crate::console::ConsoleDispatchSystem] is.
+#[derive(Default)]
+pub struct ViewportDispatchSystem;
+
+impl<'a> RunNow<'a> for ViewportDispatchSystem {
+	fn run_now(&mut self, world: &'a World) {
+		let (windows, pending) = {
+			let state = world.fetch::<crate::ImguiStatePtr>().clone();
+			let state = state.lock().unwrap();
+			(state.viewports.clone(), state.viewport_requests.clone())
+		};
+		let requests = std::mem::take(&mut *pending.lock().unwrap());
+		if requests.is_empty() {
+			return;
+		}
+
+		let events_loop = world.fetch::<EventsLoop>();
+		let mut windows = windows.lock().unwrap();
+
+		for request in requests {
+			match request {
+				ViewportRequest::Create { id, decorated } => {
+					let window = amethyst::winit::WindowBuilder::new()
+						.with_visibility(false)
+						.with_decorations(decorated)
+						.build(&events_loop);
+					if let Ok(window) = window {
+						windows.insert(id, ViewportWindow { window });
+					}
+				},
+				ViewportRequest::Destroy { id } => {
+					windows.remove(&id);
+				},
+				ViewportRequest::Show { id } => {
+					if let Some(vp) = windows.get(&id) {
+						vp.window.show();
+					}
+				},
+				ViewportRequest::SetPos { id, pos } => {
+					if let Some(vp) = windows.get(&id) {
+						vp.window.set_position((pos[0] as f64, pos[1] as f64).into());
+					}
+				},
+				ViewportRequest::SetSize { id, size } => {
+					if let Some(vp) = windows.get(&id) {
+						vp.window.set_inner_size((size[0] as f64, size[1] as f64).into());
+					}
+				},
+				ViewportRequest::SetTitle { id, title } => {
+					if let Some(vp) = windows.get(&id) {
+						vp.window.set_title(&title);
+					}
+				},
+			}
+		}
+	}
+
+	fn setup(&mut self, _world: &mut World) {}
+}
+
+/// Registers a [ViewportPlatform] with `context` so dragged-out panels get real
+/// windows while `ConfigFlags::VIEWPORTS_ENABLE` is set, as it is by default under the
+/// `docking` feature. Returns the shared window map, for [ImguiState::viewports] to
+/// track alongside `textures`, and the request queue the backend fills and
+/// [ViewportDispatchSystem] drains each frame.
+pub(crate) fn register(context: &mut imgui::Context, _world: &World) -> (Viewports, PendingRequests) {
+	let viewports = Viewports::default();
+	let pending = PendingRequests::default();
+	context.set_platform_backend(ViewportPlatform::new(viewports.clone(), pending.clone()));
+	(viewports, pending)
+}